@@ -55,7 +55,8 @@
 
 extern crate iron;
 
-use iron::{Handler, IronResult, Request, Response, Chain};
+use iron::{Handler, IronResult, Request, Response, Chain, BeforeMiddleware, AfterMiddleware, Headers};
+use iron::method::Method;
 
 /// Specifies the type of middleware you are passing to the routes.
 ///
@@ -101,6 +102,270 @@ pub enum Middleware {
     /// ```
 
     AfterMiddleware(Box<iron::AfterMiddleware>),
+
+    /// ```rust,no_run
+    /// middlefiddle! {
+    ///     router => some_router,
+    ///     routes => {
+    ///         // Some routes…
+    ///     },
+    ///     middleware => {
+    ///         Middleware::AroundMiddleware => middleware::SomeMiddleware,
+    ///     },
+    /// };
+    /// ```
+    ///
+    /// Holds a closure rather than a `Box<iron::AroundMiddleware>`: unlike
+    /// `BeforeMiddleware`/`AfterMiddleware`, `AroundMiddleware::around` consumes
+    /// `self` by value and iron has no `impl AroundMiddleware for
+    /// Box<AroundMiddleware>`, so a boxed trait object can't be linked onto a
+    /// [`Chain`] generically. [`Middleware::around`](#method.around) builds the
+    /// closure while the concrete middleware type is still known. It's a
+    /// `FnOnce`, not a `Fn`, because `around` can only ever run once per chain.
+    AroundMiddleware(Box<FnOnce(Box<Handler>) -> Box<Handler> + Send + Sync>),
+}
+
+impl Middleware {
+    /// Wraps a concrete `iron::AroundMiddleware` into a `Middleware::AroundMiddleware`.
+    pub fn around<M>(inner: M) -> Middleware
+        where M: iron::AroundMiddleware + Send + Sync + 'static
+    {
+        Middleware::AroundMiddleware(Box::new(move |handler| inner.around(handler)))
+    }
+}
+
+/// A condition tested against the incoming [`Request`] to decide whether a
+/// gated piece of middleware should run.
+///
+/// This is used together with [`ConditionalBefore`] and the macro's `when`
+/// clause so that a single `middlefiddle!` block can apply middleware only to
+/// the requests that match (e.g. only validate a token when an `Authorization`
+/// header is present) without being split into several blocks.
+pub trait Predicate: Send + Sync {
+    /// Returns `true` when the gated middleware should run for `req`.
+    fn check(&self, req: &Request) -> bool;
+}
+
+/// Matches when a header with the given name is present on the request.
+pub struct HeaderPresent(pub String);
+
+/// Matches when the named header is present and one of its values equals the
+/// given string.
+pub struct HeaderEquals(pub String, pub String);
+
+/// Matches when the request was made with the given method.
+pub struct MethodIs(pub Method);
+
+impl HeaderPresent {
+    /// Returns `true` when `headers` carries the named header, independent of
+    /// any [`Request`].
+    pub fn matches(&self, headers: &Headers) -> bool {
+        headers.get_raw(&self.0).is_some()
+    }
+}
+
+impl HeaderEquals {
+    /// Returns `true` when `headers` carries the named header and one of its
+    /// values equals the expected string, independent of any [`Request`].
+    pub fn matches(&self, headers: &Headers) -> bool {
+        match headers.get_raw(&self.0) {
+            Some(values) => values.iter().any(|v| v.as_slice() == self.1.as_bytes()),
+            None => false,
+        }
+    }
+}
+
+impl MethodIs {
+    /// Returns `true` when `method` is the expected method, independent of any
+    /// [`Request`].
+    pub fn matches(&self, method: &Method) -> bool {
+        &self.0 == method
+    }
+}
+
+impl Predicate for HeaderPresent {
+    fn check(&self, req: &Request) -> bool {
+        self.matches(&req.headers)
+    }
+}
+
+impl Predicate for HeaderEquals {
+    fn check(&self, req: &Request) -> bool {
+        self.matches(&req.headers)
+    }
+}
+
+impl Predicate for MethodIs {
+    fn check(&self, req: &Request) -> bool {
+        self.matches(&req.method)
+    }
+}
+
+/// Wraps a [`BeforeMiddleware`] so that it only runs when `pred` matches the
+/// request. When the predicate returns `false` the inner middleware is skipped
+/// and the request is passed through untouched.
+pub struct ConditionalBefore {
+    pub pred: Box<Predicate>,
+    pub inner: Box<BeforeMiddleware>,
+}
+
+impl BeforeMiddleware for ConditionalBefore {
+    fn before(&self, req: &mut Request) -> IronResult<()> {
+        if self.pred.check(req) {
+            self.inner.before(req)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A set of compiled path prefixes used to limit where scoped middleware runs.
+///
+/// A prefix of `"/"` (which compiles to no segments) matches every request, so
+/// it can be used as a catch-all.
+#[derive(Clone)]
+pub struct Scope {
+    prefixes: Vec<Vec<String>>,
+}
+
+impl Scope {
+    pub fn new() -> Self {
+        Scope { prefixes: Vec::new() }
+    }
+
+    /// Adds a path prefix to the scope, compiling it into segments.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefixes.push(compile_prefix(prefix));
+        self
+    }
+
+    /// Returns `true` when `path` begins with any of the scope's prefixes.
+    pub fn matches(&self, path: &[&str]) -> bool {
+        self.prefixes.iter().any(|prefix| {
+            prefix.len() <= path.len() && prefix.iter().zip(path).all(|(p, s)| p == s)
+        })
+    }
+}
+
+fn compile_prefix(prefix: &str) -> Vec<String> {
+    prefix.split('/').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect()
+}
+
+/// Converts the argument to a `scope => …` clause into a [`Scope`], so a single
+/// prefix string or a list of them can both be used.
+pub trait IntoScope {
+    fn into_scope(self) -> Scope;
+}
+
+impl<'a> IntoScope for &'a str {
+    fn into_scope(self) -> Scope {
+        Scope::new().prefix(self)
+    }
+}
+
+impl<'a> IntoScope for Vec<&'a str> {
+    fn into_scope(self) -> Scope {
+        self.into_iter().fold(Scope::new(), |scope, p| scope.prefix(p))
+    }
+}
+
+impl IntoScope for Scope {
+    fn into_scope(self) -> Scope {
+        self
+    }
+}
+
+/// Wraps a [`BeforeMiddleware`] so it only runs for requests whose path begins
+/// with one of the scope's prefixes.
+///
+/// This is a standalone, request-time gate for when the matching routes aren't
+/// known up front (e.g. they're registered outside a `middlefiddle!` block).
+/// The macro's own `scope => <prefix>` clause no longer builds one of these:
+/// it checks each listed route's own path against the scope once, at
+/// registration time, rather than re-checking the incoming request's path on
+/// every request — gating on the request's path let a route whose literal
+/// path fell outside the declared scope silently no-op its middleware instead
+/// of failing loudly.
+pub struct ScopedBefore {
+    pub scope: Scope,
+    pub inner: Box<BeforeMiddleware>,
+}
+
+impl BeforeMiddleware for ScopedBefore {
+    fn before(&self, req: &mut Request) -> IronResult<()> {
+        if self.scope.matches(&req.url.path()) {
+            self.inner.before(req)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A reusable, named set of middleware that can be shared across several
+/// `middlefiddle!` blocks.
+///
+/// Because `BeforeMiddleware`/`AfterMiddleware` instances are consumed per-chain
+/// by `link_before`/`link_after`, the stack stores closure factories that mint a
+/// fresh [`Middleware`] for every route it is applied to rather than the
+/// instances themselves. Define a stack once and hand it to the macro with
+/// `middleware => my_stack`:
+///
+/// ```rust,no_run
+/// let authenticated = MiddlewareStack::new()
+///     .before(|| middleware::auth::TokenValidity)
+///     .after(|| middleware::log::Access);
+///
+/// middlefiddle! {
+///     router => some_router,
+///     routes => {
+///         // Some routes…
+///     },
+///     middleware => authenticated,
+/// };
+/// ```
+pub struct MiddlewareStack {
+    factories: Vec<Box<Fn() -> Box<Middleware> + Send + Sync>>,
+}
+
+impl MiddlewareStack {
+    pub fn new() -> Self {
+        MiddlewareStack { factories: Vec::new() }
+    }
+
+    /// Adds a `BeforeMiddleware` factory to the stack.
+    pub fn before<F, M>(mut self, f: F) -> Self
+        where F: Fn() -> M + Send + Sync + 'static, M: BeforeMiddleware
+    {
+        self.factories.push(Box::new(move || {
+            Box::new(Middleware::BeforeMiddleware(Box::new(f())))
+        }));
+        self
+    }
+
+    /// Adds an `AfterMiddleware` factory to the stack.
+    pub fn after<F, M>(mut self, f: F) -> Self
+        where F: Fn() -> M + Send + Sync + 'static, M: AfterMiddleware
+    {
+        self.factories.push(Box::new(move || {
+            Box::new(Middleware::AfterMiddleware(Box::new(f())))
+        }));
+        self
+    }
+
+    /// Adds an `AroundMiddleware` factory to the stack.
+    pub fn around<F, M>(mut self, f: F) -> Self
+        where F: Fn() -> M + Send + Sync + 'static, M: iron::AroundMiddleware + Send + Sync + 'static
+    {
+        self.factories.push(Box::new(move || {
+            Box::new(Middleware::around(f()))
+        }));
+        self
+    }
+
+    /// Produces a fresh set of boxed [`Middleware`] values for one route.
+    pub fn build(&self) -> Vec<Box<Middleware>> {
+        self.factories.iter().map(|f| f()).collect()
+    }
 }
 
 #[doc(hidden)]
@@ -127,6 +392,9 @@ impl Middlefiddle {
                 },
                 Middleware::AfterMiddleware(i) => {
                     chain.link_after(i);
+                },
+                Middleware::AroundMiddleware(i) => {
+                    chain.link_around(i);
                 }
             }
         }
@@ -170,6 +438,9 @@ impl Handler for Middlefiddle {
 ///         // An example `AfterMiddleware`:
 ///         Middleware::AfterMiddleware => middleware::SomeAfterMiddleware,
 ///
+///         // An example `AroundMiddleware`:
+///         Middleware::AroundMiddleware => middleware::SomeAroundMiddleware,
+///
 ///         // There can be as many of these as you like…
 ///     },
 /// };
@@ -177,6 +448,33 @@ impl Handler for Middlefiddle {
 ///
 /// ## Notes
 ///
+/// - A middleware entry accepts a bare closure in place of a named type, with no wrapper needed:
+/// iron itself provides `BeforeMiddleware`/`AfterMiddleware` impls for any
+/// `Fn(&mut Request) -> IronResult<()>` / `Fn(&mut Request, Response) -> IronResult<Response>` (e.g.
+/// `Middleware::BeforeMiddleware => |req: &mut Request| { /* … */ Ok(()) }`).
+///
+/// - An optional `scope => <prefix>` clause (placed just after `router =>`) declares that every
+/// route in the block is expected to fall under the prefix, and panics at registration time if one
+/// doesn't — catching a typo'd or copy/pasted route immediately instead of leaving its middleware
+/// silently skipped on every request it serves. A prefix of `"/"` matches everything, and a `Vec` of
+/// prefixes (e.g. `scope => vec!["/admin", "/api"]`) allows any of them. The `scope => ...` form of
+/// the macro only accepts `Middleware::BeforeMiddleware` entries, so an `AfterMiddleware`/
+/// `AroundMiddleware` entry is rejected at the macro boundary rather than failing deep in a
+/// generated trait bound. For gating middleware on the *incoming request's* path instead — e.g. for
+/// routes registered outside of a `middlefiddle!` block — wrap it in
+/// [`ScopedBefore`](struct.ScopedBefore.html) directly.
+///
+/// - A middleware entry may carry an optional `, when <predicate>` clause so that it only runs for
+/// requests matching the [`Predicate`](trait.Predicate.html) (for example
+/// `Middleware::BeforeMiddleware => middleware::auth::TokenValidity, when
+/// iron_middlefiddle::HeaderPresent("Authorization".to_string())`). The comma is required because a
+/// bare `when` can't directly follow an arbitrary expression in a `macro_rules!` matcher. `when`
+/// entries are gated by a [`ConditionalBefore`](struct.ConditionalBefore.html) and so must be
+/// `Middleware::BeforeMiddleware`
+/// — using it on an `AfterMiddleware`/`AroundMiddleware` entry is rejected by the macro itself.
+/// Built-in predicates are [`HeaderPresent`](struct.HeaderPresent.html),
+/// [`HeaderEquals`](struct.HeaderEquals.html) and [`MethodIs`](struct.MethodIs.html).
+///
 /// - The formatting of the contents of `routes => { ... }` intentionally matches that of the router
 /// crate's own [router macro](https://docs.rs/router/0.5.1/router/macro.router.html) in an effort
 /// to make any potential refactoring easier.
@@ -191,6 +489,60 @@ impl Handler for Middlefiddle {
 ///     - `options`
 ///     - `any`
 
+// Dispatches a `Middleware::$variant => $handler [, when $predicate]` entry to
+// the right constructor. Taking `$variant` as a bare ident (rather than
+// capturing the whole `Middleware::$variant => ...` as a single `expr`) keeps
+// the token literal so this can match on it, since an `AroundMiddleware` entry
+// needs `Middleware::around(...)` rather than the `BeforeMiddleware`/
+// `AfterMiddleware` variants' `Middleware::$variant(Box::new(...))` form —
+// `AroundMiddleware::around` consumes `self` by value, so there's no boxed
+// trait object to build it from generically.
+//
+// Only `BeforeMiddleware` has a `$inner, $pred` arm: `ConditionalBefore` only
+// implements `BeforeMiddleware`, so a `when` clause on an `AfterMiddleware`/
+// `AroundMiddleware` entry has no matching rule here and fails with a plain
+// macro-expansion error instead of a confusing trait-bound one.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __middlefiddle_wrap {
+    (BeforeMiddleware, $inner:expr) => {
+        $crate::Middleware::BeforeMiddleware(Box::new($inner))
+    };
+    (BeforeMiddleware, $inner:expr, $pred:expr) => {
+        $crate::Middleware::BeforeMiddleware(Box::new($crate::ConditionalBefore {
+            pred: Box::new($pred),
+            inner: Box::new($inner),
+        }))
+    };
+    (AfterMiddleware, $inner:expr) => {
+        $crate::Middleware::AfterMiddleware(Box::new($inner))
+    };
+    (AroundMiddleware, $inner:expr) => {
+        $crate::Middleware::around($inner)
+    };
+}
+
+// Registers `$handler` on `$router` under `$route`/`$id`, picking the router
+// method from `$method`'s runtime string value. Shared by every `middlefiddle!`
+// arm so the eight-method dispatch isn't copy-pasted once per arm.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __middlefiddle_dispatch {
+    ($router:expr, $method:expr, $route:expr, $handler:expr, $id:expr) => {
+        match $method.as_ref() {
+            "get" => { $router.get($route, $handler, $id); },
+            "post" => { $router.post($route, $handler, $id); },
+            "put" => { $router.put($route, $handler, $id); },
+            "delete" => { $router.delete($route, $handler, $id); },
+            "head" => { $router.head($route, $handler, $id); },
+            "patch" => { $router.patch($route, $handler, $id); },
+            "options" => { $router.options($route, $handler, $id); },
+            "any" => { $router.any($route, $handler, $id); },
+            _ => {}
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! middlefiddle {
     (
@@ -203,7 +555,7 @@ macro_rules! middlefiddle {
         },
         middleware => {
             $(
-                $middleware_type:expr => $middleware_handler:expr
+                Middleware::$middleware_type:ident => $middleware_handler:expr $(, when $predicate:expr)*
             ),*
             $(,)*
         }
@@ -226,25 +578,348 @@ macro_rules! middlefiddle {
             let mut middleware = Vec::new();
 
             $(
-                middleware.push(Box::new($middleware_type(Box::new($middleware_handler))));
+                middleware.push(Box::new(__middlefiddle_wrap!($middleware_type, $middleware_handler $(, $predicate)*)));
             )*
 
-            let route_id = route.id.take();
-            let route_route = route.route.take();
-            let route_handler = route.handler.take();
-            let middleware_chain = Middlefiddle::new(route_handler.unwrap(), middleware);
-
-            match route.method.as_ref() {
-                "get" => { $router.get(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                "post" => { $router.post(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                "put" => { $router.put(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                "delete" => { $router.delete(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                "head" => { $router.head(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                "patch" => { $router.patch(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                "options" => { $router.options(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                "any" => { $router.any(route_route.unwrap().to_string(), middleware_chain, route_id.unwrap().to_string()); },
-                _ => {}
-            }
+            let route_id = route.id.take().unwrap().to_string();
+            let route_route = route.route.take().unwrap().to_string();
+            let route_handler = route.handler.take().unwrap();
+            let middleware_chain = Middlefiddle::new(route_handler, middleware);
+
+            __middlefiddle_dispatch!($router, route.method, route_route, middleware_chain, route_id);
+        }
+    });
+
+    (
+        router => $router:expr,
+        scope => $scope:expr,
+        routes => {
+            $(
+                $route_id:ident: $route_method:ident $route:expr => $route_handler:expr
+            ),+
+            $(,)*
+        },
+        middleware => {
+            $(
+                Middleware::BeforeMiddleware => $middleware_handler:expr
+            ),*
+            $(,)*
+        }
+        $(,)*
+    ) => ({
+        use iron_middlefiddle::{Middlefiddle, Route, IntoScope};
+
+        let scope = $scope.into_scope();
+        let mut routes = Vec::new();
+
+        $(
+            routes.push(Route {
+                id: Some(stringify!($route_id).to_string()),
+                method: stringify!($route_method).to_string(),
+                route: Some($route.to_string()),
+                handler: Some(Box::new($route_handler)),
+            });
+        )*
+
+        for mut route in routes {
+            let route_route = route.route.take().unwrap().to_string();
+
+            // Check the route's own path against the scope once, here at
+            // registration time, instead of gating the middleware behind a
+            // per-request check of the incoming path: a route whose literal
+            // path falls outside the declared scope is almost certainly a
+            // typo or copy/paste mistake, and should fail loudly now rather
+            // than silently no-op its middleware for every request it serves.
+            let route_segments: Vec<&str> = route_route.split('/').filter(|s| !s.is_empty()).collect();
+            assert!(
+                scope.matches(&route_segments),
+                "middlefiddle!: route `{}` falls outside the declared scope",
+                route_route
+            );
+
+            let mut middleware = Vec::new();
+
+            $(
+                middleware.push(Box::new($crate::Middleware::BeforeMiddleware(Box::new($middleware_handler))));
+            )*
+
+            let route_id = route.id.take().unwrap().to_string();
+            let route_handler = route.handler.take().unwrap();
+            let middleware_chain = Middlefiddle::new(route_handler, middleware);
+
+            __middlefiddle_dispatch!($router, route.method, route_route, middleware_chain, route_id);
+        }
+    });
+
+    (
+        router => $router:expr,
+        routes => {
+            $(
+                $route_id:ident: $route_method:ident $route:expr => $route_handler:expr
+            ),+
+            $(,)*
+        },
+        middleware => $stack:expr
+        $(,)*
+    ) => ({
+        use iron_middlefiddle::{Middlefiddle, Route};
+
+        let stack = $stack;
+        let mut routes = Vec::new();
+
+        $(
+            routes.push(Route {
+                id: Some(stringify!($route_id).to_string()),
+                method: stringify!($route_method).to_string(),
+                route: Some($route.to_string()),
+                handler: Some(Box::new($route_handler)),
+            });
+        )*
+
+        for mut route in routes {
+            // Mint a fresh set of middleware for each route, since each chain
+            // consumes the instances it links.
+            let middleware = stack.build();
+
+            let route_id = route.id.take().unwrap().to_string();
+            let route_route = route.route.take().unwrap().to_string();
+            let route_handler = route.handler.take().unwrap();
+            let middleware_chain = Middlefiddle::new(route_handler, middleware);
+
+            __middlefiddle_dispatch!($router, route.method, route_route, middleware_chain, route_id);
         }
     });
 }
+
+/// Builds a middleware-wrapped handler and returns it as a value instead of
+/// pushing onto an existing `Router`, so it can be mounted under a
+/// [`mount::Mount`](https://docs.rs/mount/0.3.0/mount/struct.Mount.html) or
+/// nested inside another chain.
+///
+/// The `routes => { ... }` form mints a fresh `Router` (so the `router` crate
+/// must be in scope) and returns it:
+///
+/// ```rust,no_run
+/// let api = middlefiddle_handler! {
+///     routes => {
+///         lorem: get "/lorem" => controllers::lorem::index,
+///     },
+///     middleware => {
+///         Middleware::BeforeMiddleware => middleware::auth::TokenValidity,
+///     },
+/// };
+///
+/// mount.mount("/api", api);
+/// ```
+///
+/// The `handler => ...` form wraps a single handler and returns the
+/// [`Middlefiddle`](struct.Middlefiddle.html) directly:
+///
+/// ```rust,no_run
+/// let handler = middlefiddle_handler! {
+///     handler => controllers::lorem::index,
+///     middleware => {
+///         Middleware::BeforeMiddleware => middleware::auth::TokenValidity,
+///     },
+/// };
+///
+/// mount.mount("/lorem", handler);
+/// ```
+
+#[macro_export]
+macro_rules! middlefiddle_handler {
+    (
+        routes => {
+            $(
+                $route_id:ident: $route_method:ident $route:expr => $route_handler:expr
+            ),+
+            $(,)*
+        },
+        middleware => {
+            $(
+                Middleware::$middleware_type:ident => $middleware_handler:expr
+            ),*
+            $(,)*
+        }
+        $(,)*
+    ) => ({
+        let mut router = ::router::Router::new();
+
+        middlefiddle! {
+            router => router,
+            routes => {
+                $(
+                    $route_id: $route_method $route => $route_handler
+                ),+
+            },
+            middleware => {
+                $(
+                    Middleware::$middleware_type => $middleware_handler
+                ),*
+            },
+        };
+
+        router
+    });
+
+    (
+        handler => $handler:expr,
+        middleware => {
+            $(
+                Middleware::$middleware_type:ident => $middleware_handler:expr
+            ),*
+            $(,)*
+        }
+        $(,)*
+    ) => ({
+        use iron_middlefiddle::Middlefiddle;
+
+        let mut middleware = Vec::new();
+
+        $(
+            middleware.push(Box::new(__middlefiddle_wrap!($middleware_type, $middleware_handler)));
+        )*
+
+        Middlefiddle::new($handler, middleware)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Noop;
+
+    impl BeforeMiddleware for Noop {
+        fn before(&self, _req: &mut Request) -> IronResult<()> {
+            Ok(())
+        }
+    }
+
+    struct NoopHandler;
+
+    impl Handler for NoopHandler {
+        fn handle(&self, _req: &mut Request) -> IronResult<Response> {
+            Ok(Response::new())
+        }
+    }
+
+    struct FlaggingAround(Arc<AtomicBool>);
+
+    impl iron::AroundMiddleware for FlaggingAround {
+        fn around(self, handler: Box<Handler>) -> Box<Handler> {
+            self.0.store(true, Ordering::SeqCst);
+            handler
+        }
+    }
+
+    #[test]
+    fn middleware_around_links_the_inner_middleware_onto_the_handler() {
+        let ran = Arc::new(AtomicBool::new(false));
+        let middleware = Middleware::around(FlaggingAround(ran.clone()));
+
+        let wrap = match middleware {
+            Middleware::AroundMiddleware(wrap) => wrap,
+            _ => panic!("Middleware::around did not produce an AroundMiddleware"),
+        };
+
+        assert!(!ran.load(Ordering::SeqCst));
+        wrap(Box::new(NoopHandler));
+        assert!(ran.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn middleware_stack_build_produces_one_middleware_per_factory() {
+        let stack = MiddlewareStack::new()
+            .before(|| Noop)
+            .before(|| Noop)
+            .after(|| Noop2);
+
+        assert_eq!(stack.build().len(), 3);
+    }
+
+    struct Noop2;
+
+    impl AfterMiddleware for Noop2 {
+        fn after(&self, _req: &mut Request, res: Response) -> IronResult<Response> {
+            Ok(res)
+        }
+    }
+
+    #[test]
+    fn scope_matches_prefix_and_nested_paths() {
+        let scope = Scope::new().prefix("/admin");
+
+        assert!(scope.matches(&["admin"]));
+        assert!(scope.matches(&["admin", "users"]));
+        assert!(!scope.matches(&["api"]));
+    }
+
+    #[test]
+    fn scope_root_prefix_matches_everything() {
+        let scope = Scope::new().prefix("/");
+
+        assert!(scope.matches(&[]));
+        assert!(scope.matches(&["anything", "at", "all"]));
+    }
+
+    #[test]
+    fn scope_matches_any_of_several_prefixes() {
+        let scope = Scope::new().prefix("/admin").prefix("/api");
+
+        assert!(scope.matches(&["admin"]));
+        assert!(scope.matches(&["api", "v1"]));
+        assert!(!scope.matches(&["public"]));
+    }
+
+    #[test]
+    fn str_into_scope_compiles_a_single_prefix() {
+        let scope = "/admin".into_scope();
+
+        assert!(scope.matches(&["admin"]));
+        assert!(!scope.matches(&["api"]));
+    }
+
+    #[test]
+    fn vec_into_scope_compiles_every_prefix() {
+        let scope = vec!["/admin", "/api"].into_scope();
+
+        assert!(scope.matches(&["admin"]));
+        assert!(scope.matches(&["api"]));
+        assert!(!scope.matches(&["public"]));
+    }
+
+    #[test]
+    fn header_present_matches_only_when_the_header_is_set() {
+        let pred = HeaderPresent("X-Token".to_string());
+
+        let mut headers = Headers::new();
+        assert!(!pred.matches(&headers));
+
+        headers.set_raw("X-Token", vec![b"anything".to_vec()]);
+        assert!(pred.matches(&headers));
+    }
+
+    #[test]
+    fn header_equals_matches_only_the_expected_value() {
+        let pred = HeaderEquals("X-Token".to_string(), "secret".to_string());
+
+        let mut headers = Headers::new();
+        headers.set_raw("X-Token", vec![b"wrong".to_vec()]);
+        assert!(!pred.matches(&headers));
+
+        headers.set_raw("X-Token", vec![b"secret".to_vec()]);
+        assert!(pred.matches(&headers));
+    }
+
+    #[test]
+    fn method_is_matches_only_the_expected_method() {
+        let pred = MethodIs(Method::Post);
+
+        assert!(pred.matches(&Method::Post));
+        assert!(!pred.matches(&Method::Get));
+    }
+}
+
@@ -0,0 +1,52 @@
+#[macro_use]
+extern crate iron_middlefiddle;
+extern crate iron;
+extern crate router;
+
+use iron::{Handler, IronResult, Request, Response, BeforeMiddleware};
+
+struct NoopHandler;
+
+impl Handler for NoopHandler {
+    fn handle(&self, _req: &mut Request) -> IronResult<Response> {
+        Ok(Response::new())
+    }
+}
+
+struct NoopBefore;
+
+impl BeforeMiddleware for NoopBefore {
+    fn before(&self, _req: &mut Request) -> IronResult<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn middlefiddle_handler_routes_form_returns_a_router() {
+    let api = middlefiddle_handler! {
+        routes => {
+            lorem: get "/lorem" => NoopHandler,
+        },
+        middleware => {
+            Middleware::BeforeMiddleware => NoopBefore,
+        },
+    };
+
+    // The `routes => { ... }` form mints and returns a `router::Router` ready
+    // to be mounted, rather than pushing onto a caller-supplied one.
+    let _: router::Router = api;
+}
+
+#[test]
+fn middlefiddle_handler_handler_form_returns_a_middlefiddle() {
+    let handler = middlefiddle_handler! {
+        handler => NoopHandler,
+        middleware => {
+            Middleware::BeforeMiddleware => NoopBefore,
+        },
+    };
+
+    // The `handler => ...` form wraps a single handler and returns the
+    // `Middlefiddle` chain directly, without needing a `Router` in scope.
+    let _: iron_middlefiddle::Middlefiddle = handler;
+}
@@ -0,0 +1,59 @@
+#[macro_use]
+extern crate iron_middlefiddle;
+extern crate iron;
+extern crate router;
+
+use iron::{Handler, IronResult, Request, Response, BeforeMiddleware};
+use router::Router;
+
+struct NoopHandler;
+
+impl Handler for NoopHandler {
+    fn handle(&self, _req: &mut Request) -> IronResult<Response> {
+        Ok(Response::new())
+    }
+}
+
+struct NoopBefore;
+
+impl BeforeMiddleware for NoopBefore {
+    fn before(&self, _req: &mut Request) -> IronResult<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn scope_accepts_a_route_within_the_declared_prefix() {
+    let mut router = Router::new();
+
+    middlefiddle! {
+        router => router,
+        scope => "/admin",
+        routes => {
+            secret: get "/admin/secret" => NoopHandler,
+        },
+        middleware => {
+            Middleware::BeforeMiddleware => NoopBefore,
+        },
+    };
+}
+
+// A route outside the declared scope used to silently leave its middleware a
+// no-op for every request (the gate ran against the *incoming* request's
+// path, not the route's own), rather than failing at registration time.
+#[test]
+#[should_panic(expected = "falls outside the declared scope")]
+fn scope_rejects_a_route_outside_the_declared_prefix() {
+    let mut router = Router::new();
+
+    middlefiddle! {
+        router => router,
+        scope => "/admin",
+        routes => {
+            other: get "/other" => NoopHandler,
+        },
+        middleware => {
+            Middleware::BeforeMiddleware => NoopBefore,
+        },
+    };
+}